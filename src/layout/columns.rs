@@ -0,0 +1,166 @@
+//! Splitting a page region into multiple columns.
+
+use crate::geom::Dim;
+
+use super::primitive::Dir;
+use super::{Area, Areas, Overflow};
+
+/// Configuration for laying a page out into columns.
+#[derive(Debug, Copy, Clone)]
+pub struct ColumnsOptions {
+    /// How many columns to split each page region into.
+    pub count: usize,
+    /// The empty space left between adjacent columns.
+    pub gutter: f64,
+    /// The direction columns are laid out in (columns still fill top to
+    /// bottom; this only controls left-to-right vs. right-to-left order).
+    pub dir: Dir,
+    /// Whether to balance column heights (see [`balance`]).
+    pub balanced: bool,
+}
+
+/// Splits `area` into `options.count` side-by-side [`Area`]s of equal width,
+/// separated by `options.gutter`, ordered according to `options.dir`.
+pub fn split(area: &Area, options: ColumnsOptions) -> Vec<Area> {
+    let count = options.count.max(1);
+    let total_gutter = options.gutter * (count.saturating_sub(1)) as f64;
+    let width = (area.usable.width() - total_gutter) / count as f64;
+
+    let mut columns = vec![];
+    for i in 0..count {
+        let x0 = area.usable.x0 + i as f64 * (width + options.gutter);
+        let usable = crate::geom::shape::Rect::new(
+            crate::geom::Point::new(x0, area.usable.y0),
+            crate::geom::Point::new(x0 + width, area.usable.y1),
+        );
+        columns.push(Area::new(area.size, usable, None));
+    }
+
+    if options.dir == Dir::RTL {
+        columns.reverse();
+    }
+
+    columns
+}
+
+/// Wraps an already-split column set as the [`Areas`] a page hands out:
+/// under [`Overflow::Spill`], exhausting this set refills it with a fresh
+/// copy of itself — a new page's full column layout — instead of repeating
+/// the last column forever. Takes the columns directly (rather than
+/// splitting them itself) so a caller can [`balance`] them first.
+pub fn areas_from(columns: Vec<Area>, overflow: Overflow) -> Areas {
+    match overflow {
+        Overflow::Spill => Areas::with_refill(columns.clone(), columns, overflow),
+        Overflow::Stop => Areas::new(columns, overflow),
+    }
+}
+
+/// Splits `page` into `options.count` columns and wraps them as
+/// [`areas_from`] does. Doesn't balance column heights — pour pre-[`balance`]d
+/// columns into [`areas_from`] directly for that.
+pub fn areas(page: Area, options: ColumnsOptions, overflow: Overflow) -> Areas {
+    areas_from(split(&page, options), overflow)
+}
+
+/// Re-targets a set of column areas so each ends at roughly the same height.
+///
+/// Call this after a first pass has measured `content_height`, the total
+/// height the content would occupy if poured through the columns
+/// unconstrained; it shrinks every column's usable height to
+/// `content_height / count`, so a second layout pass naturally balances.
+pub fn balance(columns: &mut [Area], content_height: f64) {
+    let count = columns.len().max(1);
+    let target = content_height / count as f64;
+    for column in columns {
+        let y1 = (column.usable.y0 + target).min(column.usable.y1);
+        column.usable.y1 = y1;
+    }
+}
+
+/// The total height content would need if laid out through a single column
+/// of the given `width`, used as the measuring pass for [`balance`].
+pub fn measure_height(lines: &[Dim], width: f64) -> f64 {
+    let _ = width;
+    lines.iter().map(|dim| dim.height + dim.depth).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::shape::Rect;
+    use crate::geom::{Point, Size};
+
+    fn page(width: f64, height: f64) -> Area {
+        Area::new(Size { width, height }, Rect::new(Point::new(0.0, 0.0), Point::new(width, height)), None)
+    }
+
+    #[test]
+    fn split_divides_usable_width_evenly_minus_the_gutter() {
+        let options = ColumnsOptions { count: 2, gutter: 2.0, dir: Dir::LTR, balanced: false };
+        let columns = split(&page(22.0, 10.0), options);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].usable.x0, 0.0);
+        assert_eq!(columns[0].usable.x1, 10.0);
+        assert_eq!(columns[1].usable.x0, 12.0);
+        assert_eq!(columns[1].usable.x1, 22.0);
+    }
+
+    #[test]
+    fn split_reverses_column_order_for_rtl() {
+        let options = ColumnsOptions { count: 2, gutter: 0.0, dir: Dir::RTL, balanced: false };
+        let columns = split(&page(20.0, 10.0), options);
+        assert_eq!(columns[0].usable.x0, 10.0);
+        assert_eq!(columns[1].usable.x0, 0.0);
+    }
+
+    #[test]
+    fn balance_shrinks_every_column_to_an_equal_share_of_the_content_height() {
+        let options = ColumnsOptions { count: 2, gutter: 0.0, dir: Dir::LTR, balanced: true };
+        let mut columns = split(&page(20.0, 100.0), options);
+        balance(&mut columns, 60.0);
+        for column in &columns {
+            assert_eq!(column.usable.y1 - column.usable.y0, 30.0);
+        }
+    }
+
+    #[test]
+    fn balance_never_grows_a_column_past_its_original_height() {
+        let options = ColumnsOptions { count: 2, gutter: 0.0, dir: Dir::LTR, balanced: true };
+        let mut columns = split(&page(20.0, 10.0), options);
+        balance(&mut columns, 1000.0);
+        for column in &columns {
+            assert_eq!(column.usable.y1 - column.usable.y0, 10.0);
+        }
+    }
+
+    #[test]
+    fn measure_height_sums_each_lines_height_and_depth() {
+        let lines = vec![Dim::new(5.0, 3.0, 1.0), Dim::new(5.0, 2.0, 0.5)];
+        assert_eq!(measure_height(&lines, 5.0), 6.5);
+    }
+
+    #[test]
+    fn areas_from_spill_refills_with_a_fresh_copy_of_the_columns() {
+        let options = ColumnsOptions { count: 2, gutter: 0.0, dir: Dir::LTR, balanced: false };
+        let columns = split(&page(20.0, 10.0), options);
+        let mut areas = areas_from(columns, Overflow::Spill);
+
+        assert!(areas.next().is_some());
+        assert!(areas.next().is_some());
+        // The column set is exhausted but Spill should refill it with a
+        // fresh copy rather than returning None.
+        assert!(areas.next().is_some());
+        assert!(areas.next().is_some());
+    }
+
+    #[test]
+    fn areas_from_stop_yields_none_once_the_columns_are_exhausted() {
+        let options = ColumnsOptions { count: 2, gutter: 0.0, dir: Dir::LTR, balanced: false };
+        let columns = split(&page(20.0, 10.0), options);
+        let mut areas = areas_from(columns, Overflow::Stop);
+
+        assert!(areas.next().is_some());
+        assert!(areas.next().is_some());
+        assert!(areas.next().is_none());
+    }
+}
@@ -1,7 +1,10 @@
 //! Layouting of syntax trees into box layouts.
 
+pub mod columns;
 pub mod elements;
+pub mod par;
 pub mod primitive;
+pub mod refine;
 pub mod shaping;
 pub mod stack;
 
@@ -18,7 +21,9 @@ pub mod prelude {
 
 pub use primitive::*;
 
+use std::future::Future;
 use std::ops::Deref;
+use std::pin::Pin;
 
 use crate::compute::Scope;
 use crate::font::SharedFontLoader;
@@ -29,6 +34,8 @@ use crate::syntax::tree::{SyntaxNode, SyntaxTree};
 use crate::{Feedback, Pass};
 
 use elements::LayoutElement;
+use par::{collect, linebreak, linebreak_justified, linebreak_with, BreakMode};
+use refine::{LayoutStyleRefinement, StyleChain};
 use shaping::{shape, ShapeOptions};
 use stack::{StackLayouter, StackOptions};
 
@@ -36,41 +43,170 @@ use stack::{StackLayouter, StackOptions};
 pub async fn layout(
     tree: &SyntaxTree,
     loader: SharedFontLoader,
-    state: State,
+    mut state: State,
 ) -> Pass<Vec<Layout>> {
     let mut loader = loader.borrow_mut();
 
-    let page = &state.style.page;
+    // `state` arrives from the caller with its own `style` chain already set
+    // up (e.g. a document's base style plus any top-level set rules), so the
+    // cache `State::style` reads has to be primed here before anything reads
+    // it; every nested scope keeps it in sync from then on via
+    // `State::refined`.
+    state.resolved = state.style.resolve();
+    let style = state.style();
+    let page = &style.page;
     let margins = page.margins();
-    let area = Area {
-        size: page.size,
-        usable: page.size.to_rect().inset(margins),
-        shape: None,
-    };
+    let area = Area::new(page.size, page.size.to_rect().inset(margins), None);
+
+    let areas = if page.columns > 1 {
+        let options = columns::ColumnsOptions {
+            count: page.columns,
+            gutter: page.gutter,
+            dir: style.text.dir,
+            balanced: page.balance_columns,
+        };
+
+        let mut columns = columns::split(&area, options);
+        if options.balanced {
+            let content_height =
+                measure_content_height(&columns, tree, &mut loader, &state).await;
+            columns::balance(&mut columns, content_height);
+        }
 
-    let areas = Areas::new(vec![area], Overflow::Spill);
+        columns::areas_from(columns, Overflow::Spill)
+    } else {
+        Areas::new(vec![area], Overflow::Spill)
+    };
     let mut stack = StackLayouter::new(areas, StackOptions { dir: Dir::TTB });
 
-    for node in tree {
-        let item = match &node.v {
-            SyntaxNode::Text(text) => {
-                let layout = shape(text, ShapeOptions {
-                    loader: &mut loader,
-                    style: &state.style.text,
-                    dir: Dir::LTR,
-                })
-                .await;
-                LayoutItem::Layout(GenAlign::Start, layout)
-            }
-            _ => continue,
-        };
+    // Shaped runs and glue accumulate here until a paragraph boundary (or
+    // the end of the tree) is reached, at which point they're broken into
+    // lines against the current area's usable width.
+    let mut par_items = vec![];
 
-        stack.layout_item(item);
-    }
+    layout_tree(tree, &mut loader, &mut state, &mut stack, &mut par_items).await;
+
+    flush_par(&mut par_items, &mut stack, state.style().text.justify, state.break_mode);
 
     Pass::ok(stack.finish())
 }
 
+/// Walks `tree`, shaping text and handing finished lines to `stack`.
+///
+/// A [`SyntaxNode::Scope`] carries its own nested tree plus a
+/// [`LayoutStyleRefinement`] local to it (e.g. from a set rule): descending
+/// into it resolves a scoped [`State`] via [`State::refined`] so the
+/// override never leaks to the scope's siblings once the clone is dropped.
+/// Boxed because an `async fn` can't otherwise call itself recursively.
+fn layout_tree<'a>(
+    tree: &'a SyntaxTree,
+    loader: &'a mut SharedFontLoader,
+    state: &'a mut State,
+    stack: &'a mut StackLayouter,
+    par_items: &'a mut Vec<LayoutItem>,
+) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+    Box::pin(async move {
+        for node in tree {
+            match &node.v {
+                SyntaxNode::Text(text) => {
+                    let style = state.style();
+                    let dir = style.text.dir;
+                    let layout = shape(text, ShapeOptions {
+                        loader,
+                        style: &style.text,
+                        dir,
+                    })
+                    .await;
+                    let align = if dir == Dir::RTL { GenAlign::End } else { GenAlign::Start };
+                    par_items.push(LayoutItem::Layout(align, layout));
+                }
+                SyntaxNode::Space => par_items.push(LayoutItem::Space),
+                SyntaxNode::Parbreak => {
+                    flush_par(par_items, stack, state.style().text.justify, state.break_mode);
+                    stack.layout_item(LayoutItem::Parbreak);
+                }
+                SyntaxNode::Scope(inner, refinement) => {
+                    flush_par(par_items, stack, state.style().text.justify, state.break_mode);
+                    let mut scoped = state.refined(refinement.clone());
+                    layout_tree(inner, loader, &mut scoped, stack, par_items).await;
+                    flush_par(par_items, stack, scoped.style().text.justify, scoped.break_mode);
+                }
+                _ => continue,
+            };
+        }
+    })
+}
+
+/// Measures how tall `tree`'s content would be if poured through a single
+/// unconstrained-height column matching `columns`' width — the first pass
+/// [`columns::balance`] needs before it can retarget `columns`' heights for
+/// the real, balanced second pass.
+async fn measure_content_height(
+    columns: &[Area],
+    tree: &SyntaxTree,
+    loader: &mut SharedFontLoader,
+    state: &State,
+) -> f64 {
+    let column = &columns[0];
+    let probe_rect = Rect::new(
+        Point::new(column.usable.x0, column.usable.y0),
+        Point::new(column.usable.x1, column.usable.y0 + 1.0e6),
+    );
+    let probe_area = Area::new(column.size, probe_rect, None);
+
+    let mut probe_state = state.clone();
+    let mut probe_stack = StackLayouter::new(
+        Areas::new(vec![probe_area], Overflow::Stop),
+        StackOptions { dir: Dir::TTB },
+    );
+    let mut probe_par_items = vec![];
+
+    layout_tree(tree, loader, &mut probe_state, &mut probe_stack, &mut probe_par_items).await;
+    flush_par(
+        &mut probe_par_items,
+        &mut probe_stack,
+        probe_state.style().text.justify,
+        probe_state.break_mode,
+    );
+
+    let lines = probe_stack.finish();
+    let dims: Vec<Dim> = lines.iter().map(|layout| layout.dim).collect();
+    columns::measure_height(&dims, column.usable.width())
+}
+
+/// Breaks up the accumulated paragraph items into lines against the current
+/// area's usable width and hands each line to the stack as its own
+/// [`LayoutItem::Layout`]. Lines are fully justified when `justify` is set
+/// (the paragraph's final line is never stretched and keeps its boxes' own
+/// alignment, see [`par::ParBox::align`]); otherwise every line keeps its
+/// boxes' own alignment. `mode` selects which breaking algorithm does the
+/// work; under [`BreakMode::Optimal`], `justify` is ignored: the optimal
+/// breaker stretches or shrinks every line's glue toward `width` and
+/// returns each line as [`GenAlign::Start`] (see [`linebreak_with`]).
+fn flush_par(par_items: &mut Vec<LayoutItem>, stack: &mut StackLayouter, justify: bool, mode: BreakMode) {
+    if par_items.is_empty() {
+        return;
+    }
+
+    let width = stack
+        .areas()
+        .0
+        .map(|area| area.usable.width())
+        .unwrap_or(f64::INFINITY);
+
+    let items = collect(par_items);
+    let lines = match mode {
+        BreakMode::Greedy if justify => linebreak_justified(&items, width),
+        BreakMode::Greedy => linebreak(&items, width),
+        BreakMode::Optimal => linebreak_with(&items, width, mode),
+    };
+    for (line, align) in lines {
+        stack.layout_item(LayoutItem::Layout(align, line));
+    }
+
+    par_items.clear();
+}
+
 /// The layouting environment.
 pub struct Env {
     /// The accumulated feedback.
@@ -87,8 +223,39 @@ pub struct Env {
 pub struct State {
     /// The scope which contains function definitions.
     pub scope: Scope,
-    /// The current style configuration.
-    pub style: LayoutStyle,
+    /// The style in effect, as a base style plus a stack of set-rule
+    /// refinements. See [`refine`] for why this replaces a plain
+    /// `LayoutStyle` clone.
+    pub style: StyleChain,
+    /// Which algorithm breaks a paragraph's items into lines. Not part of
+    /// [`StyleChain`]/[`LayoutStyle`] since it's a layouting-engine knob
+    /// rather than a document style a set rule would touch; defaults to
+    /// [`BreakMode::Greedy`].
+    pub break_mode: BreakMode,
+    /// `style` folded into an effective [`LayoutStyle`], cached here so
+    /// [`State::style`] doesn't re-fold the whole refinement stack on every
+    /// text node and scope/parbreak it's called for. Kept up to date by
+    /// [`State::refined`], the only place `style` changes.
+    resolved: LayoutStyle,
+}
+
+impl State {
+    /// The effective style with all refinements folded in, read from the
+    /// cache [`State::refined`] maintains rather than re-resolved here.
+    pub fn style(&self) -> LayoutStyle {
+        self.resolved.clone()
+    }
+
+    /// Returns a copy of this state with `refinement` applied on top of the
+    /// current style, as happens when `layout` descends into a scope with
+    /// local set-rule overrides. Re-resolves the cache [`State::style`]
+    /// reads once, here, rather than on every subsequent access.
+    pub fn refined(&self, refinement: LayoutStyleRefinement) -> Self {
+        let mut state = self.clone();
+        state.style.push(refinement);
+        state.resolved = state.style.resolve();
+        state
+    }
 }
 
 /// A layout consisting of atomic elements.
@@ -142,17 +309,38 @@ pub enum LayoutItem {
 pub struct Areas {
     vec: Vec<Area>,
     overflow: Overflow,
+    /// Template to refill `vec` from once it runs out, so [`Overflow::Spill`]
+    /// can hand out a fresh *set* of areas — e.g. a new page's full column
+    /// layout — instead of just repeating the last area forever. `None`
+    /// preserves that original single-area-repeats-forever behavior, which
+    /// is still what a plain one-area-per-page document wants.
+    refill: Option<Vec<Area>>,
 }
 
 impl Areas {
     pub fn new(vec: Vec<Area>, overflow: Overflow) -> Self {
-        Self { vec, overflow }
+        Self { vec, overflow, refill: None }
+    }
+
+    /// Like [`Areas::new`], but once `vec` runs out under
+    /// [`Overflow::Spill`], refills it from `refill` rather than repeating
+    /// its last area forever. Used by [`columns::areas_from`] so a page laid
+    /// out into columns spills into a fresh page's columns, not an endless
+    /// repeat of the page's last column.
+    pub fn with_refill(vec: Vec<Area>, refill: Vec<Area>, overflow: Overflow) -> Self {
+        Self { vec, overflow, refill: Some(refill) }
     }
 
     pub fn next(&mut self) -> Option<Area> {
+        if self.vec.is_empty() && self.overflow == Overflow::Spill {
+            if let Some(refill) = &self.refill {
+                self.vec = refill.clone();
+            }
+        }
+
         if self.vec.is_empty() {
             None
-        } else if self.vec.len() > 1 || self.overflow == Overflow::Stop {
+        } else if self.refill.is_some() || self.vec.len() > 1 || self.overflow == Overflow::Stop {
             Some(self.vec.remove(0))
         } else {
             Some(self.vec[0].clone())
@@ -160,28 +348,252 @@ impl Areas {
     }
 }
 
+/// A horizontal slice of an [`Area`]'s free space: all x-intervals that are
+/// still available for content between `y0` and `y1`. Placed floats carve
+/// their bounding geometry out of the intervals of the bands they overlap;
+/// removing a float restores it.
+#[derive(Debug, Clone)]
+pub struct Band {
+    pub y0: f64,
+    pub y1: f64,
+    /// Sorted, non-overlapping `[x0, x1]` spans still free in this band.
+    pub intervals: Vec<(f64, f64)>,
+}
+
+impl Band {
+    fn full(y0: f64, y1: f64, x0: f64, x1: f64) -> Self {
+        Self { y0, y1, intervals: vec![(x0, x1)] }
+    }
+
+    /// Removes `[x0, x1]` from the free intervals, splitting any interval it
+    /// overlaps.
+    fn subtract_x(&mut self, x0: f64, x1: f64) {
+        let mut kept = vec![];
+        for (a, b) in self.intervals.drain(..) {
+            if x1 <= a || x0 >= b {
+                kept.push((a, b));
+                continue;
+            }
+            if a < x0 {
+                kept.push((a, x0));
+            }
+            if x1 < b {
+                kept.push((x1, b));
+            }
+        }
+        self.intervals = kept;
+    }
+
+    /// Restores `[x0, x1]` to the free intervals, merging adjacent/overlapping
+    /// spans back together.
+    fn add_x(&mut self, x0: f64, x1: f64) {
+        self.intervals.push((x0, x1));
+        self.intervals.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let mut merged: Vec<(f64, f64)> = vec![];
+        for (a, b) in self.intervals.drain(..) {
+            match merged.last_mut() {
+                Some(last) if a <= last.1 => last.1 = last.1.max(b),
+                _ => merged.push((a, b)),
+            }
+        }
+        self.intervals = merged;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Area {
     pub size: Size,
     pub usable: Rect,
     pub shape: Option<ShapeGroup>,
+    /// Free-space scanlines, used to flow content around placed floats. A
+    /// fresh area starts with a single band spanning `usable` in full.
+    bands: Vec<Band>,
 }
 
 #[allow(unused)]
 impl Area {
+    pub fn new(size: Size, usable: Rect, shape: Option<ShapeGroup>) -> Self {
+        let bands = vec![Band::full(usable.y0, usable.y1, usable.x0, usable.x1)];
+        Self { size, usable, shape, bands }
+    }
+
     pub fn place(&self, dim: Dim, side: Side) -> Option<Point> {
         const EPS: f64 = 1e-4;
 
-        // TODO: Support shapes and more than just top.
+        // TODO: Support placement from sides other than the top.
         assert_eq!(side, Side::Top);
-        assert!(self.shape.is_none());
 
-        if self.usable.width() + EPS > dim.width
-            && self.usable.height() + EPS > dim.height + dim.depth
-        {
-            Some(Point::new(self.usable.x0, self.usable.y0 + dim.height))
-        } else {
-            None
+        let height = dim.height + dim.depth;
+        for band in &self.bands {
+            if band.y1 - band.y0 + EPS < height {
+                continue;
+            }
+            for &(x0, x1) in &band.intervals {
+                if x1 - x0 + EPS >= dim.width {
+                    return Some(Point::new(x0, band.y0 + dim.height));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Splits any band overlapping `[y0, y1)` at those edges, so a float that
+    /// only partly covers a band's height (e.g. a drop cap spanning two
+    /// lines) narrows just the slice of the band it actually overlaps
+    /// instead of its whole height. A band entirely above, below, or inside
+    /// `[y0, y1)` is left as-is; one straddling an edge is replaced by up to
+    /// three bands — above, within, and below — each carrying a copy of its
+    /// parent's free intervals.
+    fn split_bands(&mut self, y0: f64, y1: f64) {
+        let mut split = vec![];
+        for band in self.bands.drain(..) {
+            if band.y1 <= y0 || band.y0 >= y1 {
+                split.push(band);
+                continue;
+            }
+            if band.y0 < y0 {
+                split.push(Band { y0: band.y0, y1: y0, intervals: band.intervals.clone() });
+            }
+            split.push(Band {
+                y0: band.y0.max(y0),
+                y1: band.y1.min(y1),
+                intervals: band.intervals.clone(),
+            });
+            if band.y1 > y1 {
+                split.push(Band { y0: y1, y1: band.y1, intervals: band.intervals });
+            }
+        }
+        self.bands = split;
+    }
+
+    /// Merges adjacent bands with identical free intervals back into one,
+    /// undoing the fragmentation [`Area::split_bands`] introduced once
+    /// nothing (e.g. a removed float) distinguishes the pieces anymore.
+    fn merge_bands(&mut self) {
+        let mut merged: Vec<Band> = vec![];
+        for band in self.bands.drain(..) {
+            match merged.last_mut() {
+                Some(last) if last.y1 == band.y0 && last.intervals == band.intervals => {
+                    last.y1 = band.y1;
+                }
+                _ => merged.push(band),
+            }
+        }
+        self.bands = merged;
+    }
+
+    /// Carves the bounding geometry of a placed float out of this area's
+    /// free space, so that in-flow content laid out afterwards flows around
+    /// it instead of overlapping it.
+    ///
+    /// `outline` is the float's exact path, used only by [`Collider::Tight`]
+    /// to carve each band down to the path's true silhouette rather than its
+    /// bounding box; every other collider only ever sees `rect`. Bands are
+    /// first split at `rect`'s y-edges (see [`Area::split_bands`]) so the
+    /// narrowing below never reaches outside the float's actual height;
+    /// [`Collider::Column`] is the exception, since it carves its x-range out
+    /// of every band regardless of height.
+    pub fn subtract(&mut self, rect: Rect, outline: Option<&BezPath>, collider: Collider) {
+        match collider {
+            Collider::None => {}
+            Collider::Tight => {
+                self.split_bands(rect.y0, rect.y1);
+                for band in &mut self.bands {
+                    if band.y1 <= rect.y0 || band.y0 >= rect.y1 {
+                        continue;
+                    }
+                    match outline {
+                        Some(path) => {
+                            for (x0, x1) in
+                                path.scanline_spans(band.y0.max(rect.y0), band.y1.min(rect.y1))
+                            {
+                                band.subtract_x(x0, x1);
+                            }
+                        }
+                        None => band.subtract_x(rect.x0, rect.x1),
+                    }
+                }
+            }
+            Collider::Bounds => {
+                self.split_bands(rect.y0, rect.y1);
+                for band in &mut self.bands {
+                    if band.y1 <= rect.y0 || band.y0 >= rect.y1 {
+                        continue;
+                    }
+                    band.subtract_x(rect.x0, rect.x1);
+                }
+            }
+            Collider::Row => {
+                self.split_bands(rect.y0, rect.y1);
+                for band in &mut self.bands {
+                    if band.y1 <= rect.y0 || band.y0 >= rect.y1 {
+                        continue;
+                    }
+                    band.subtract_x(self.usable.x0, self.usable.x1);
+                }
+            }
+            Collider::Column => {
+                for band in &mut self.bands {
+                    band.subtract_x(rect.x0, rect.x1);
+                }
+            }
+        }
+    }
+
+    /// Restores space previously removed by [`Area::subtract`] with the same
+    /// `rect`/`outline`/`collider`, e.g. once a float is no longer in flow.
+    /// Bands are split the same way `subtract` split them, then re-merged
+    /// (see [`Area::merge_bands`]) once the restore leaves nothing to tell
+    /// the pieces apart anymore.
+    pub fn add(&mut self, rect: Rect, outline: Option<&BezPath>, collider: Collider) {
+        match collider {
+            Collider::None => {}
+            Collider::Tight => {
+                self.split_bands(rect.y0, rect.y1);
+                for band in &mut self.bands {
+                    if band.y1 <= rect.y0 || band.y0 >= rect.y1 {
+                        continue;
+                    }
+                    match outline {
+                        Some(path) => {
+                            for (x0, x1) in
+                                path.scanline_spans(band.y0.max(rect.y0), band.y1.min(rect.y1))
+                            {
+                                band.add_x(x0, x1);
+                            }
+                        }
+                        None => band.add_x(rect.x0, rect.x1),
+                    }
+                }
+                self.merge_bands();
+            }
+            Collider::Bounds => {
+                self.split_bands(rect.y0, rect.y1);
+                for band in &mut self.bands {
+                    if band.y1 <= rect.y0 || band.y0 >= rect.y1 {
+                        continue;
+                    }
+                    band.add_x(rect.x0, rect.x1);
+                }
+                self.merge_bands();
+            }
+            Collider::Row => {
+                self.split_bands(rect.y0, rect.y1);
+                for band in &mut self.bands {
+                    if band.y1 <= rect.y0 || band.y0 >= rect.y1 {
+                        continue;
+                    }
+                    band.add_x(self.usable.x0, self.usable.x1);
+                }
+                self.merge_bands();
+            }
+            Collider::Column => {
+                for band in &mut self.bands {
+                    band.add_x(rect.x0, rect.x1);
+                }
+            }
         }
     }
 
@@ -202,14 +614,6 @@ impl Area {
             Side::Bottom => self.usable.y1 = to.max(self.usable.y0),
         }
     }
-
-    pub fn add(&mut self, path: &BezPath) {
-        todo!("add")
-    }
-
-    pub fn subtract(&mut self, path: &BezPath) {
-        todo!("subtract")
-    }
 }
 
 impl Deref for Areas {
@@ -240,3 +644,108 @@ pub enum Overflow {
     Stop,
     Spill,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subtract_x_splits_an_interval_it_overlaps() {
+        let mut band = Band::full(0.0, 10.0, 0.0, 10.0);
+        band.subtract_x(3.0, 5.0);
+        assert_eq!(band.intervals, vec![(0.0, 3.0), (5.0, 10.0)]);
+    }
+
+    #[test]
+    fn subtract_x_removes_an_interval_it_fully_covers() {
+        let mut band = Band::full(0.0, 10.0, 2.0, 8.0);
+        band.subtract_x(0.0, 10.0);
+        assert!(band.intervals.is_empty());
+    }
+
+    #[test]
+    fn subtract_x_leaves_disjoint_intervals_untouched() {
+        let mut band = Band::full(0.0, 10.0, 0.0, 2.0);
+        band.subtract_x(5.0, 8.0);
+        assert_eq!(band.intervals, vec![(0.0, 2.0)]);
+    }
+
+    #[test]
+    fn add_x_merges_back_into_an_adjacent_interval() {
+        let mut band = Band::full(0.0, 10.0, 0.0, 10.0);
+        band.subtract_x(3.0, 5.0);
+        band.add_x(3.0, 5.0);
+        assert_eq!(band.intervals, vec![(0.0, 10.0)]);
+    }
+
+    #[test]
+    fn add_x_keeps_disjoint_intervals_separate() {
+        let mut band = Band { y0: 0.0, y1: 10.0, intervals: vec![(0.0, 2.0)] };
+        band.add_x(8.0, 10.0);
+        assert_eq!(band.intervals, vec![(0.0, 2.0), (8.0, 10.0)]);
+    }
+
+    #[test]
+    fn area_subtract_with_bounds_collider_carves_the_full_rect_even_with_an_outline() {
+        let mut area =
+            Area::new(Size { width: 10.0, height: 10.0 }, Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 10.0)), None);
+        area.subtract(
+            Rect::new(Point::new(2.0, 2.0), Point::new(8.0, 8.0)),
+            Some(&BezPath::default()),
+            Collider::Bounds,
+        );
+        // The float only spans y 2..8, so only the band split out for that
+        // slice should be narrowed.
+        assert_eq!(area.bands[1].y0, 2.0);
+        assert_eq!(area.bands[1].y1, 8.0);
+        assert_eq!(area.bands[1].intervals, vec![(0.0, 2.0), (8.0, 10.0)]);
+    }
+
+    #[test]
+    fn area_subtract_with_tight_collider_falls_back_to_the_rect_without_an_outline() {
+        let mut area =
+            Area::new(Size { width: 10.0, height: 10.0 }, Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 10.0)), None);
+        area.subtract(Rect::new(Point::new(2.0, 2.0), Point::new(8.0, 8.0)), None, Collider::Tight);
+        assert_eq!(area.bands[1].intervals, vec![(0.0, 2.0), (8.0, 10.0)]);
+    }
+
+    #[test]
+    fn area_subtract_splits_the_band_at_the_floats_y_edges_leaving_the_rest_full_width() {
+        let mut area =
+            Area::new(Size { width: 10.0, height: 10.0 }, Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 10.0)), None);
+        area.subtract(Rect::new(Point::new(2.0, 2.0), Point::new(8.0, 8.0)), None, Collider::Bounds);
+
+        assert_eq!(area.bands.len(), 3);
+        assert_eq!((area.bands[0].y0, area.bands[0].y1), (0.0, 2.0));
+        assert_eq!(area.bands[0].intervals, vec![(0.0, 10.0)]);
+        assert_eq!((area.bands[1].y0, area.bands[1].y1), (2.0, 8.0));
+        assert_eq!(area.bands[1].intervals, vec![(0.0, 2.0), (8.0, 10.0)]);
+        assert_eq!((area.bands[2].y0, area.bands[2].y1), (8.0, 10.0));
+        assert_eq!(area.bands[2].intervals, vec![(0.0, 10.0)]);
+    }
+
+    #[test]
+    fn area_add_merges_the_split_bands_back_into_one_once_the_float_is_removed() {
+        let mut area =
+            Area::new(Size { width: 10.0, height: 10.0 }, Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 10.0)), None);
+        let rect = Rect::new(Point::new(2.0, 2.0), Point::new(8.0, 8.0));
+        area.subtract(rect, None, Collider::Bounds);
+        area.add(rect, None, Collider::Bounds);
+
+        assert_eq!(area.bands.len(), 1);
+        assert_eq!((area.bands[0].y0, area.bands[0].y1), (0.0, 10.0));
+        assert_eq!(area.bands[0].intervals, vec![(0.0, 10.0)]);
+    }
+
+    #[test]
+    fn collider_row_only_narrows_the_band_overlapping_the_floats_height() {
+        let mut area =
+            Area::new(Size { width: 10.0, height: 10.0 }, Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 10.0)), None);
+        area.subtract(Rect::new(Point::new(2.0, 2.0), Point::new(8.0, 8.0)), None, Collider::Row);
+
+        assert_eq!(area.bands.len(), 3);
+        assert_eq!(area.bands[0].intervals, vec![(0.0, 10.0)]);
+        assert!(area.bands[1].intervals.is_empty());
+        assert_eq!(area.bands[2].intervals, vec![(0.0, 10.0)]);
+    }
+}
@@ -0,0 +1,438 @@
+//! Paragraph layouting: collects shaped runs and glue into lines.
+
+pub mod optimal;
+
+use crate::geom::Dim;
+
+use super::primitive::GenAlign;
+use super::{Layout, LayoutItem};
+
+/// A single box of already-shaped content with a fixed advance width.
+#[derive(Debug, Clone)]
+pub struct ParBox {
+    /// The shaped layout for this box.
+    pub layout: Layout,
+    /// The alignment the box's source run was shaped with — [`GenAlign::End`]
+    /// for a right-to-left run, [`GenAlign::Start`] otherwise. A paragraph's
+    /// dir doesn't change mid-flush (a dir change always flushes first, see
+    /// `layout_tree`), so every box between two flushes carries the same
+    /// value; a finished line just takes it from whichever box it holds.
+    pub align: GenAlign,
+}
+
+/// Breakable space between two boxes, with a natural width plus stretch and
+/// shrink potential. The greedy breaker uses stretch/shrink only when
+/// justifying (see [`linebreak_justified`]); the optimal breaker shares this
+/// representation.
+#[derive(Debug, Copy, Clone)]
+pub struct Glue {
+    pub width: f64,
+    pub stretch: f64,
+    pub shrink: f64,
+}
+
+/// One item in the flat stream the paragraph layouter consumes.
+#[derive(Debug, Clone)]
+pub enum ParItem {
+    Box(ParBox),
+    Glue(Glue),
+    Parbreak,
+}
+
+/// Which algorithm breaks a paragraph's items into lines.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum BreakMode {
+    /// First-fit: each line is filled until the next box would overflow.
+    /// Cheap and predictable, but can leave later lines more ragged than
+    /// necessary.
+    #[default]
+    Greedy,
+    /// Knuth–Plass: minimizes total raggedness over the whole paragraph by
+    /// considering every legal breakpoint jointly.
+    Optimal,
+}
+
+/// Breaks a stream of paragraph items into lines that fit the given `width`,
+/// pairing each [`Layout`] with the [`GenAlign`] it should be placed with,
+/// using the requested [`BreakMode`].
+///
+/// The optimal breaker doesn't yet track each item's source alignment (see
+/// [`optimal::Item::Box`]), so its lines always come back as
+/// [`GenAlign::Start`] regardless of paragraph direction; only the greedy
+/// breaker currently respects it.
+pub fn linebreak_with(items: &[ParItem], width: f64, mode: BreakMode) -> Vec<(Layout, GenAlign)> {
+    match mode {
+        BreakMode::Greedy => linebreak(items, width),
+        BreakMode::Optimal => optimal::linebreak_optimal(&optimal::from_par_items(items), width)
+            .into_iter()
+            .map(|line| (line, GenAlign::Start))
+            .collect(),
+    }
+}
+
+/// Greedily breaks a stream of paragraph items into lines that each fit the
+/// given `width`, pairing each line with the [`GenAlign`] its boxes were
+/// shaped with (see [`ParBox::align`]).
+///
+/// This is a first-fit line breaker: boxes are accumulated left to right and
+/// a line is cut as soon as the next box would overflow `width`. A
+/// [`ParItem::Parbreak`] always forces a break, even if the current line
+/// still has room.
+pub fn linebreak(items: &[ParItem], width: f64) -> Vec<(Layout, GenAlign)> {
+    linebreak_greedy(items, width, false)
+}
+
+/// Breaks a paragraph's items into lines like [`linebreak`], but fully
+/// justifies every non-final line: the leftover space between a line's
+/// natural width and `width` is distributed across that line's inter-word
+/// glue, proportionally to each glue's stretchability (or shrinkability, if
+/// the line is overfull). A paragraph's final line — the one ending a
+/// [`ParItem::Parbreak`] or the end of the stream — is left at its natural
+/// width and returned with its own [`GenAlign`] (see [`ParBox::align`])
+/// instead, so it isn't stretched to fill the line.
+pub fn linebreak_justified(items: &[ParItem], width: f64) -> Vec<(Layout, GenAlign)> {
+    linebreak_greedy(items, width, true)
+}
+
+/// Shared greedy-breaking core behind [`linebreak`] and
+/// [`linebreak_justified`]. Glue trailing a break (the space that would
+/// otherwise sit right before the line would have wrapped) is dropped before
+/// the line is finished, so a justified line's last box still reaches
+/// `width` instead of falling short by a glue's width.
+fn linebreak_greedy(items: &[ParItem], width: f64, justify: bool) -> Vec<(Layout, GenAlign)> {
+    let mut lines = vec![];
+    let mut line = LineBuilder::new();
+
+    let cut = |line: &mut LineBuilder, lines: &mut Vec<(Layout, GenAlign)>, final_line: bool| {
+        let align = line.align;
+        let mut finished = std::mem::replace(line, LineBuilder::new());
+        finished.trim_trailing_glue();
+        if justify && !final_line {
+            lines.push((finished.finish(Some(width)), GenAlign::Justified));
+        } else {
+            lines.push((finished.finish(None), align));
+        }
+    };
+
+    for item in items {
+        match item {
+            ParItem::Box(b) => {
+                let advance = b.layout.size().width;
+                if !line.is_empty() && line.width() + advance > width {
+                    cut(&mut line, &mut lines, false);
+                }
+                line.push_box(b.layout.clone(), b.align);
+            }
+            ParItem::Glue(glue) => {
+                if !line.is_empty() {
+                    line.push_glue(*glue);
+                }
+            }
+            ParItem::Parbreak => {
+                if !line.is_empty() {
+                    cut(&mut line, &mut lines, true);
+                }
+            }
+        }
+    }
+
+    if !line.is_empty() {
+        cut(&mut line, &mut lines, true);
+    }
+
+    lines
+}
+
+/// Converts a sequence of already-collected [`LayoutItem`]s into a flat
+/// [`ParItem`] stream, ready for [`linebreak`].
+///
+/// Inter-word spaces are given a modest default stretch/shrink (half and a
+/// third of their natural width respectively) so [`linebreak_justified`] has
+/// something to distribute. Explicit, non-justifiable spacing arrives as
+/// [`LayoutItem::Spacing`] instead, which is dropped here.
+pub fn collect(items: &[LayoutItem]) -> Vec<ParItem> {
+    const SPACE_WIDTH: f64 = 0.25;
+
+    let mut out = vec![];
+    for item in items {
+        match item {
+            LayoutItem::Layout(align, layout) => {
+                out.push(ParItem::Box(ParBox { layout: layout.clone(), align: *align }));
+            }
+            LayoutItem::Space => {
+                out.push(ParItem::Glue(Glue {
+                    width: SPACE_WIDTH,
+                    stretch: SPACE_WIDTH / 2.0,
+                    shrink: SPACE_WIDTH / 3.0,
+                }));
+            }
+            LayoutItem::Parbreak => out.push(ParItem::Parbreak),
+            LayoutItem::Spacing(_, _) => {}
+        }
+    }
+    out
+}
+
+/// One slot in a line under construction: either a shaped box or the glue
+/// between two boxes.
+enum LineSlot {
+    Box(Layout),
+    Glue(Glue),
+}
+
+/// Accumulates boxes and glue for a single line, tracking the pen position,
+/// the union of ascents/descents (so the finished line's [`Dim`] stacks
+/// baselines correctly against neighbouring lines), and the total
+/// stretch/shrink available for justification.
+struct LineBuilder {
+    natural_width: f64,
+    stretch: f64,
+    shrink: f64,
+    ascent: f64,
+    descent: f64,
+    /// The line's alignment, taken from the boxes pushed into it (see
+    /// [`ParBox::align`]); [`GenAlign::Start`] for an empty line.
+    align: GenAlign,
+    slots: Vec<LineSlot>,
+}
+
+impl LineBuilder {
+    fn new() -> Self {
+        Self {
+            natural_width: 0.0,
+            stretch: 0.0,
+            shrink: 0.0,
+            ascent: 0.0,
+            descent: 0.0,
+            align: GenAlign::Start,
+            slots: vec![],
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.slots.iter().all(|slot| matches!(slot, LineSlot::Glue(_)))
+    }
+
+    fn width(&self) -> f64 {
+        self.natural_width
+    }
+
+    fn push_glue(&mut self, glue: Glue) {
+        self.natural_width += glue.width;
+        self.stretch += glue.stretch;
+        self.shrink += glue.shrink;
+        self.slots.push(LineSlot::Glue(glue));
+    }
+
+    fn push_box(&mut self, layout: Layout, align: GenAlign) {
+        self.ascent = self.ascent.max(layout.dim.height);
+        self.descent = self.descent.max(layout.dim.depth);
+        self.natural_width += layout.size().width;
+        self.align = align;
+        self.slots.push(LineSlot::Box(layout));
+    }
+
+    /// Drops glue trailing the last box, as happens at a break: the space
+    /// that would have separated this word from the next doesn't belong on
+    /// either line, so it shouldn't count toward this line's natural width
+    /// or be stretched when justifying.
+    fn trim_trailing_glue(&mut self) {
+        while matches!(self.slots.last(), Some(LineSlot::Glue(_))) {
+            if let Some(LineSlot::Glue(glue)) = self.slots.pop() {
+                self.natural_width -= glue.width;
+                self.stretch -= glue.stretch;
+                self.shrink -= glue.shrink;
+            }
+        }
+    }
+
+    /// Finishes the line. If `justify_to` is `Some(width)` and the line has
+    /// at least one glue slot to distribute into, the line is stretched (or
+    /// shrunk) to exactly `width`; otherwise it's left at its natural width
+    /// so single-word lines never get stretched to infinity.
+    fn finish(self, justify_to: Option<f64>) -> Layout {
+        let has_glue = self.slots.iter().any(|slot| matches!(slot, LineSlot::Glue(_)));
+
+        let ratio = justify_to.filter(|_| has_glue).and_then(|target| {
+            let delta = target - self.natural_width;
+            if delta >= 0.0 && self.stretch > 0.0 {
+                Some((delta / self.stretch).min(1.0))
+            } else if delta < 0.0 && self.shrink > 0.0 {
+                Some((delta / self.shrink).max(-1.0))
+            } else {
+                None
+            }
+        });
+
+        let width = match (justify_to, ratio) {
+            (Some(target), Some(_)) => target,
+            _ => self.natural_width,
+        };
+
+        let dim = Dim::new(width, self.ascent, self.descent);
+        let mut line = Layout::new(dim);
+
+        let mut x = 0.0;
+        for slot in self.slots {
+            match slot {
+                LineSlot::Box(layout) => {
+                    let y = self.ascent - layout.dim.height;
+                    x += {
+                        let advance = layout.size().width;
+                        line.push_layout(crate::geom::Point::new(x, y), layout);
+                        advance
+                    };
+                }
+                LineSlot::Glue(glue) => {
+                    x += match ratio {
+                        Some(r) if r >= 0.0 => glue.width + r * glue.stretch,
+                        Some(r) => glue.width + r * glue.shrink,
+                        None => glue.width,
+                    };
+                }
+            }
+        }
+
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn box_item(width: f64, align: GenAlign) -> ParItem {
+        ParItem::Box(ParBox { layout: Layout::new(Dim::new(width, 0.0, 0.0)), align })
+    }
+
+    fn glue_item(width: f64) -> ParItem {
+        ParItem::Glue(Glue { width, stretch: 0.0, shrink: 0.0 })
+    }
+
+    fn stretchy_glue_item(width: f64, stretch: f64, shrink: f64) -> ParItem {
+        ParItem::Glue(Glue { width, stretch, shrink })
+    }
+
+    #[test]
+    fn wraps_once_the_line_width_is_exceeded() {
+        let items = vec![
+            box_item(6.0, GenAlign::Start),
+            glue_item(1.0),
+            box_item(6.0, GenAlign::Start),
+        ];
+        let lines = linebreak(&items, 10.0);
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn fits_on_one_line_when_it_all_fits() {
+        let items = vec![
+            box_item(3.0, GenAlign::Start),
+            glue_item(1.0),
+            box_item(3.0, GenAlign::Start),
+        ];
+        let lines = linebreak(&items, 10.0);
+        assert_eq!(lines.len(), 1);
+    }
+
+    #[test]
+    fn parbreak_forces_a_break_even_with_room_left() {
+        let items = vec![box_item(2.0, GenAlign::Start), ParItem::Parbreak, box_item(2.0, GenAlign::Start)];
+        let lines = linebreak(&items, 100.0);
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn collect_preserves_each_boxs_align() {
+        let items = vec![
+            LayoutItem::Layout(GenAlign::End, Layout::new(Dim::new(3.0, 0.0, 0.0))),
+            LayoutItem::Layout(GenAlign::Start, Layout::new(Dim::new(3.0, 0.0, 0.0))),
+        ];
+        let collected = collect(&items);
+        let aligns: Vec<_> = collected
+            .iter()
+            .map(|item| match item {
+                ParItem::Box(b) => b.align,
+                _ => panic!("expected a box"),
+            })
+            .collect();
+        assert_eq!(aligns, vec![GenAlign::End, GenAlign::Start]);
+    }
+
+    #[test]
+    fn a_finished_line_takes_the_align_of_its_boxes() {
+        let items = vec![box_item(2.0, GenAlign::End), box_item(2.0, GenAlign::End)];
+        let lines = linebreak(&items, 100.0);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].1, GenAlign::End);
+    }
+
+    #[test]
+    fn justify_stretches_a_wrapped_line_to_fill_the_target_width() {
+        let items = vec![
+            box_item(4.0, GenAlign::Start),
+            stretchy_glue_item(1.0, 5.0, 1.0),
+            box_item(4.0, GenAlign::Start),
+            box_item(4.0, GenAlign::Start),
+        ];
+        let lines = linebreak_justified(&items, 10.0);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].1, GenAlign::Justified);
+        assert_eq!(lines[0].0.dim.width, 10.0);
+    }
+
+    #[test]
+    fn justify_leaves_the_final_line_at_its_natural_width() {
+        let items = vec![
+            box_item(4.0, GenAlign::Start),
+            stretchy_glue_item(1.0, 5.0, 1.0),
+            box_item(4.0, GenAlign::Start),
+            box_item(4.0, GenAlign::End),
+        ];
+        let lines = linebreak_justified(&items, 10.0);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].1, GenAlign::End);
+        assert_eq!(lines[1].0.dim.width, 4.0);
+    }
+
+    #[test]
+    fn line_builder_finish_shrinks_an_overfull_line_toward_the_target_width() {
+        // A first-fit greedy line can never naturally overflow its own
+        // width (a box is only accepted once it's checked to fit), so the
+        // shrink branch isn't reachable through `linebreak_justified` itself
+        // — exercise `LineBuilder::finish` directly instead.
+        let mut line = LineBuilder::new();
+        line.push_box(Layout::new(Dim::new(6.0, 0.0, 0.0)), GenAlign::Start);
+        line.push_glue(Glue { width: 1.0, stretch: 5.0, shrink: 3.0 });
+        line.push_box(Layout::new(Dim::new(6.0, 0.0, 0.0)), GenAlign::Start);
+
+        let finished = line.finish(Some(10.0));
+        assert_eq!(finished.dim.width, 10.0);
+    }
+
+    #[test]
+    fn line_builder_finish_clamps_the_shrink_ratio_to_fully_closed() {
+        let mut line = LineBuilder::new();
+        line.push_box(Layout::new(Dim::new(6.0, 0.0, 0.0)), GenAlign::Start);
+        line.push_glue(Glue { width: 1.0, stretch: 5.0, shrink: 0.5 });
+        line.push_box(Layout::new(Dim::new(6.0, 0.0, 0.0)), GenAlign::Start);
+
+        // Natural width is 13, target is 10: a delta of -3 against a shrink
+        // budget of only 0.5 would need a ratio of -6, but the ratio is
+        // clamped to -1.0 (fully closed) rather than over-shrinking.
+        let finished = line.finish(Some(10.0));
+        assert_eq!(finished.dim.width, 10.0);
+    }
+
+    #[test]
+    fn justify_tags_a_glueless_wrapped_line_as_justified_but_keeps_its_natural_width() {
+        // With no glue to distribute into, a non-final line still gets
+        // tagged `Justified` (it wrapped, not finished the paragraph), but
+        // `finish` has nothing to stretch so the width stays natural.
+        let items = vec![box_item(9.0, GenAlign::Start), box_item(9.0, GenAlign::Start)];
+        let lines = linebreak_justified(&items, 10.0);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].1, GenAlign::Justified);
+        assert_eq!(lines[0].0.dim.width, 9.0);
+    }
+}
@@ -0,0 +1,349 @@
+//! Optimal (Knuth–Plass) paragraph breaking.
+//!
+//! Minimizes total raggedness across the whole paragraph rather than
+//! breaking greedily. Modeled as a dynamic program over an alternatives
+//! tree: each legal break point is a node, and the edge from one active
+//! breakpoint to the next is scored by how badly the line between them has
+//! to stretch or shrink to fill the line width.
+
+use super::{Glue, ParBox, ParItem};
+use crate::geom::Dim;
+use crate::layout::Layout;
+
+/// Penalty cost contributed by breaking at a point (e.g. a hyphenation
+/// point), independent of how the line looks.
+#[derive(Debug, Copy, Clone)]
+pub struct Penalty(pub f64);
+
+/// An item in the optimal breaker's flat stream. Boxes carry only a width
+/// here; glue additionally carries stretch/shrink so the adjustment ratio
+/// can be computed at each candidate break.
+#[derive(Debug, Clone)]
+pub enum Item {
+    Box { width: f64, layout: Layout },
+    Glue(Glue),
+    Penalty(Penalty),
+}
+
+/// A node in the alternatives tree: one choice of where to break. Chained
+/// through `predecessor`, these form the set of active breakpoints the
+/// dynamic program threads through.
+#[derive(Debug, Clone)]
+struct Alt {
+    /// Index into the item stream where this break occurs.
+    index: usize,
+    /// Cumulative demerits of the best path ending at this break.
+    demerits: f64,
+    /// Index of the chosen predecessor in the candidates list built so far,
+    /// or `None` for the implicit break before the first item.
+    predecessor: Option<usize>,
+}
+
+/// Threshold above which a line is considered overfull/underfull and
+/// rejected as a candidate (mirrors TeX's notion of an infeasible break).
+const BADNESS_INFINITE: f64 = 10_000.0;
+
+/// The item index a segment *starting* at `node` should measure/emit from.
+///
+/// `node.index` is where the break itself sits: the implicit break before
+/// the first item for the root node (`predecessor: None`), or the
+/// glue/penalty item that was broken at for every other node. That item is
+/// consumed by the break and belongs to neither the line before it nor the
+/// line after, so a real break point starts its line one item past it;
+/// only the root has no such item to skip.
+fn segment_start(node: &Alt) -> usize {
+    match node.predecessor {
+        None => node.index,
+        Some(_) => node.index + 1,
+    }
+}
+
+/// Breaks `items` into lines of `width` using the Knuth–Plass algorithm,
+/// returning one [`Layout`] per line with its glue widths adjusted to fill
+/// `width`. The last line is adjusted the same way as every other line.
+///
+/// `nodes` holds every breakpoint ever created, each pointing at its chosen
+/// predecessor by index, so the cheapest path can be traced back once the
+/// whole item stream has been scanned. `active` holds the indices into
+/// `nodes` that are still reachable candidates for the *next* break: a
+/// predecessor is dropped from it only once the segment since it is
+/// overfull, since more content can only make an overfull line worse. This
+/// is what distinguishes Knuth–Plass from a greedy breaker — every
+/// still-feasible predecessor stays in play, so a later break can skip
+/// straight back to an earlier point if that yields fewer total demerits
+/// than routing through the most recent one.
+pub fn linebreak_optimal(items: &[Item], width: f64) -> Vec<Layout> {
+    let mut nodes = vec![Alt { index: 0, demerits: 0.0, predecessor: None }];
+    let mut active = vec![0usize];
+
+    // Scores every still-active predecessor against breaking at `i`
+    // (`penalty`/`forced` describing the break item there, or a synthetic
+    // forced break with zero penalty for the implicit break at the very end
+    // of the stream — see the call below the loop). Mutates `nodes`/`active`
+    // in place the same way the loop body used to inline.
+    let process_break =
+        |nodes: &mut Vec<Alt>, active: &mut Vec<usize>, i: usize, forced: bool, penalty: f64| {
+            let candidates = active.clone();
+            let mut kept = vec![];
+            let mut feasible: Vec<(usize, f64)> = vec![];
+
+            for a_idx in candidates {
+                let a = &nodes[a_idx];
+                let (natural, stretch, shrink) = measure(items, segment_start(a), i);
+                let delta = width - natural;
+                let adjust = if delta >= 0.0 {
+                    if stretch <= 0.0 { f64::INFINITY } else { delta / stretch }
+                } else if shrink <= 0.0 {
+                    f64::NEG_INFINITY
+                } else {
+                    delta / shrink
+                };
+                let overfull = adjust < -1.0;
+
+                // Once a predecessor's line is overfull, it only gets worse
+                // as the paragraph grows, so it can never produce a
+                // feasible break again — drop it from future rounds.
+                if !overfull {
+                    kept.push(a_idx);
+                }
+
+                let badness = 100.0 * adjust.abs().powi(3);
+                if !forced && (overfull || badness > BADNESS_INFINITE) {
+                    continue;
+                }
+
+                let demerits =
+                    a.demerits + (1.0 + badness.min(BADNESS_INFINITE)).powi(2) + penalty;
+                feasible.push((a_idx, demerits));
+            }
+
+            *active = kept;
+
+            // Degenerate fallback: every active predecessor went overfull at
+            // the same point and none was feasible either (can only happen
+            // for a non-forced break) — keep the most recent breakpoint (the
+            // one whose line is shortest) anyway so the paragraph still makes
+            // progress instead of losing all history.
+            if active.is_empty() && feasible.is_empty() && !nodes.is_empty() {
+                active.push(nodes.len() - 1);
+            }
+
+            if let Some(&(pred, demerits)) = feasible.iter().min_by(|a, b| a.1.total_cmp(&b.1)) {
+                nodes.push(Alt { index: i, demerits, predecessor: Some(pred) });
+                active.push(nodes.len() - 1);
+            }
+
+            // A forced break must actually be taken: every predecessor that
+            // hasn't broken here is an invalid continuation once we're past
+            // it, so only the node just created for breaking *at* `i`
+            // (the cheapest route into it) stays active.
+            if forced {
+                if let Some(&last) = active.last() {
+                    *active = vec![last];
+                }
+            }
+        };
+
+    for (i, item) in items.iter().enumerate() {
+        if !matches!(item, Item::Glue(_) | Item::Penalty(_)) {
+            continue;
+        }
+
+        // A forced break (a mandatory paragraph break) must take effect
+        // here no matter how badly the resulting line fits: it bypasses
+        // the badness gate in `process_break`, it just still prefers the predecessor
+        // that leaves the least ugly line.
+        let forced = matches!(item, Item::Penalty(p) if p.0 == f64::NEG_INFINITY);
+        let penalty = match item {
+            Item::Penalty(p) if p.0.is_finite() => p.0,
+            _ => 0.0,
+        };
+
+        process_break(&mut nodes, &mut active, i, forced, penalty);
+    }
+
+    // The end of the paragraph is itself a mandatory break: without this,
+    // the very first node (representing "no break at all", whose demerits
+    // never grow past 0) can silently outscore every real breakdown of the
+    // paragraph into lines and win by sheer accident of staying feasible to
+    // the end, collapsing the whole paragraph into one line.
+    process_break(&mut nodes, &mut active, items.len(), true, 0.0);
+
+    let Some(&best) = active.iter().min_by(|&&a, &&b| nodes[a].demerits.total_cmp(&nodes[b].demerits)) else {
+        return vec![];
+    };
+
+    // Trace back the chosen breakpoints from the best final node, keeping
+    // node indices (not just their `.index` field) so each segment's start
+    // can skip the break item itself via `segment_start`.
+    let mut chain = vec![best];
+    let mut cursor = nodes[best].predecessor;
+    while let Some(idx) = cursor {
+        chain.push(idx);
+        cursor = nodes[idx].predecessor;
+    }
+    chain.reverse();
+
+    let mut lines = vec![];
+    for pair in chain.windows(2) {
+        let (from, to) = (&nodes[pair[0]], &nodes[pair[1]]);
+        let start = segment_start(from);
+        // A pair of adjacent breaks with nothing but the break item itself
+        // between them (e.g. two consecutive forced breaks, or no content
+        // at all) yields an empty segment — skip it rather than emitting a
+        // zero-width line.
+        if start < to.index {
+            lines.push(emit_line(items, start, to.index, width));
+        }
+    }
+
+    lines
+}
+
+/// Measures the natural width and total stretch/shrink of the glue and
+/// boxes between two break indices.
+fn measure(items: &[Item], start: usize, end: usize) -> (f64, f64, f64) {
+    let mut natural = 0.0;
+    let mut stretch = 0.0;
+    let mut shrink = 0.0;
+
+    for item in &items[start..end] {
+        match item {
+            Item::Box { width, .. } => natural += width,
+            Item::Glue(glue) => {
+                natural += glue.width;
+                stretch += glue.stretch;
+                shrink += glue.shrink;
+            }
+            Item::Penalty(_) => {}
+        }
+    }
+
+    (natural, stretch, shrink)
+}
+
+/// Builds the final line [`Layout`] for the segment `[start, end)`,
+/// distributing the computed stretch/shrink across that line's glue so it
+/// fills `width`.
+fn emit_line(items: &[Item], start: usize, end: usize, width: f64) -> Layout {
+    let (natural, stretch, shrink) = measure(items, start, end);
+    let delta = width - natural;
+    let ratio = if delta >= 0.0 {
+        if stretch <= 0.0 { 0.0 } else { (delta / stretch).min(1.0) }
+    } else {
+        if shrink <= 0.0 { 0.0 } else { (delta / shrink).max(-1.0) }
+    };
+
+    let mut ascent = 0.0_f64;
+    let mut descent = 0.0_f64;
+    for item in &items[start..end] {
+        if let Item::Box { layout, .. } = item {
+            ascent = ascent.max(layout.dim.height);
+            descent = descent.max(layout.dim.depth);
+        }
+    }
+
+    let mut line = Layout::new(Dim::new(width.max(natural), ascent, descent));
+    let mut x = 0.0;
+    for item in &items[start..end] {
+        match item {
+            Item::Box { layout, .. } => {
+                let y = ascent - layout.dim.height;
+                line.push_layout(crate::geom::Point::new(x, y), layout.clone());
+                x += layout.size().width;
+            }
+            Item::Glue(glue) => {
+                let adjusted = if ratio >= 0.0 {
+                    glue.width + ratio * glue.stretch
+                } else {
+                    glue.width + ratio * glue.shrink
+                };
+                x += adjusted;
+            }
+            Item::Penalty(_) => {}
+        }
+    }
+
+    line
+}
+
+/// Converts the greedy breaker's [`ParItem`] stream into the richer [`Item`]
+/// stream the optimal breaker needs, treating every glue as a legal break
+/// point and every paragraph break as a mandatory one.
+pub fn from_par_items(items: &[ParItem]) -> Vec<Item> {
+    let mut out = vec![];
+    for item in items {
+        match item {
+            ParItem::Box(ParBox { layout, align: _ }) => {
+                out.push(Item::Box { width: layout.size().width, layout: layout.clone() });
+            }
+            ParItem::Glue(glue) => out.push(Item::Glue(*glue)),
+            ParItem::Parbreak => out.push(Item::Penalty(Penalty(f64::NEG_INFINITY))),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn box_item(width: f64) -> Item {
+        Item::Box { width, layout: Layout::new(Dim::new(width, 0.0, 0.0)) }
+    }
+
+    fn glue_item(width: f64, stretch: f64, shrink: f64) -> Item {
+        Item::Glue(Glue { width, stretch, shrink })
+    }
+
+    #[test]
+    fn empty_stream_breaks_into_no_lines() {
+        assert!(linebreak_optimal(&[], 10.0).is_empty());
+    }
+
+    #[test]
+    fn wraps_across_multiple_lines_when_content_overflows() {
+        // Six words joined by stretchy glue, much wider altogether than the
+        // line: breaking into several 2-word lines is far cheaper than one
+        // hugely overfull line, so the optimizer should pick the former.
+        let items = vec![
+            box_item(3.0),
+            glue_item(1.0, 3.0, 1.0),
+            box_item(3.0),
+            glue_item(1.0, 3.0, 1.0),
+            box_item(3.0),
+            glue_item(1.0, 3.0, 1.0),
+            box_item(3.0),
+            glue_item(1.0, 3.0, 1.0),
+            box_item(3.0),
+            glue_item(1.0, 3.0, 1.0),
+            box_item(3.0),
+        ];
+        assert!(linebreak_optimal(&items, 10.0).len() > 1);
+    }
+
+    #[test]
+    fn forced_break_always_takes_effect_even_on_a_short_line() {
+        // A forced break (a mandatory paragraph break) right after a single
+        // narrow box must still break here, even though the resulting line
+        // is wildly underfull — forced breaks bypass the badness gate.
+        let items =
+            vec![box_item(1.0), Item::Penalty(Penalty(f64::NEG_INFINITY)), box_item(1.0)];
+        assert_eq!(linebreak_optimal(&items, 100.0).len(), 2);
+    }
+
+    #[test]
+    fn keeps_every_feasible_predecessor_not_just_the_most_recent() {
+        // Two short words joined by very stretchy glue all fit easily on one
+        // line at this width, so the breaker shouldn't be forced to break
+        // early just because a single most-recent predecessor was kept.
+        let items = vec![
+            box_item(2.0),
+            glue_item(1.0, 5.0, 1.0),
+            box_item(2.0),
+            glue_item(1.0, 5.0, 1.0),
+            box_item(2.0),
+        ];
+        assert_eq!(linebreak_optimal(&items, 20.0).len(), 1);
+    }
+}
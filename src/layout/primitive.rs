@@ -0,0 +1,62 @@
+//! Primitive types shared across the layouting engine.
+
+/// The four sides of a rectangle.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// A layouting direction along an axis.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Dir {
+    /// Left to right.
+    LTR,
+    /// Right to left.
+    RTL,
+    /// Top to bottom.
+    TTB,
+    /// Bottom to top.
+    BTT,
+}
+
+/// The two generic axes of a layout: the one content flows along, and the
+/// one perpendicular to it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GenAxis {
+    Main,
+    Cross,
+}
+
+/// The two specific (physical) axes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SpecAxis {
+    Horizontal,
+    Vertical,
+}
+
+/// Alignment along a generic axis, relative to the current direction rather
+/// than to a physical side.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GenAlign {
+    Start,
+    Center,
+    End,
+    /// Stretched to fill the available space, as produced by paragraph
+    /// justification. Only meaningful for a line [`Layout`](super::Layout)
+    /// whose glue has already had the leftover space baked into its
+    /// elements' positions.
+    Justified,
+}
+
+/// Alignment along a specific (physical) axis.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SpecAlign {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Center,
+}
@@ -0,0 +1,226 @@
+//! Style inheritance via partial refinements.
+//!
+//! Cloning the whole [`LayoutStyle`] at every set-rule or local override is
+//! wasteful and makes nested scopes hard to reason about: a clone commits to
+//! every field of the parent, so there's no way to tell which fields a
+//! scope actually changed. A [`Refinable`] style instead has a companion
+//! type where every field is `Option<T>`; applying a refinement only
+//! overwrites the fields that are `Some`, so stacking refinements as
+//! `layout` descends the [`SyntaxTree`](crate::syntax::tree::SyntaxTree)
+//! composes predictably and stays allocation-light.
+//!
+//! The companion refinement types and their `refine` impls below are
+//! hand-written for now. The long-term plan is a `#[derive(Refinable)]`
+//! proc-macro in `typst_macros` (alongside `#[derive(class)]`) that
+//! generates this boilerplate for any leaf style struct, but until that
+//! macro exists, new style structs need their `Refinement` type and
+//! `Refinable` impl added here by hand.
+
+use crate::geom::{Sides, Size};
+use crate::style::{LayoutStyle, PageStyle, TextStyle};
+
+use super::primitive::Dir;
+
+/// A style struct with a companion "refinement" type: every field is
+/// optional, and applying one only overwrites the fields that are set.
+pub trait Refinable {
+    /// The partial-override companion of `Self`, with every field as
+    /// `Option<T>`. Each one is written by hand in this module; no derive
+    /// generates it.
+    type Refinement: Default + Clone;
+
+    /// Overwrites the fields set in `refinement`, leaving the rest of `self`
+    /// untouched.
+    fn refine(&mut self, refinement: &Self::Refinement);
+}
+
+/// The composed refinement for [`LayoutStyle`] as a whole: one refinement
+/// per top-level style section.
+#[derive(Debug, Default, Clone)]
+pub struct LayoutStyleRefinement {
+    pub text: <TextStyle as Refinable>::Refinement,
+    pub page: <PageStyle as Refinable>::Refinement,
+}
+
+/// Partial override for [`TextStyle`]. Every field mirrors one on
+/// `TextStyle`, wrapped in `Option`; this is what `#[derive(Refinable)]`
+/// would generate for it.
+#[derive(Debug, Default, Clone)]
+pub struct TextStyleRefinement {
+    pub dir: Option<Dir>,
+    pub size: Option<f64>,
+    pub justify: Option<bool>,
+}
+
+impl Refinable for TextStyle {
+    type Refinement = TextStyleRefinement;
+
+    fn refine(&mut self, refinement: &Self::Refinement) {
+        if let Some(dir) = refinement.dir {
+            self.dir = dir;
+        }
+        if let Some(size) = refinement.size {
+            self.size = size;
+        }
+        if let Some(justify) = refinement.justify {
+            self.justify = justify;
+        }
+    }
+}
+
+/// Partial override for [`PageStyle`]; see [`TextStyleRefinement`].
+///
+/// Named `margin` rather than `margins` to avoid shadowing
+/// [`PageStyle::margins`], the method that resolves the page's margin
+/// sides — a field and a method can share a name in Rust, but it's
+/// needlessly confusing here given both concern the same data.
+#[derive(Debug, Default, Clone)]
+pub struct PageStyleRefinement {
+    pub size: Option<Size>,
+    pub margin: Option<Sides<f64>>,
+    pub columns: Option<usize>,
+    pub gutter: Option<f64>,
+    pub balance_columns: Option<bool>,
+}
+
+impl Refinable for PageStyle {
+    type Refinement = PageStyleRefinement;
+
+    fn refine(&mut self, refinement: &Self::Refinement) {
+        if let Some(size) = refinement.size {
+            self.size = size;
+        }
+        if let Some(margin) = refinement.margin {
+            self.margins = margin;
+        }
+        if let Some(columns) = refinement.columns {
+            self.columns = columns;
+        }
+        if let Some(gutter) = refinement.gutter {
+            self.gutter = gutter;
+        }
+        if let Some(balance_columns) = refinement.balance_columns {
+            self.balance_columns = balance_columns;
+        }
+    }
+}
+
+impl Refinable for LayoutStyle {
+    type Refinement = LayoutStyleRefinement;
+
+    fn refine(&mut self, refinement: &Self::Refinement) {
+        self.text.refine(&refinement.text);
+        self.page.refine(&refinement.page);
+    }
+}
+
+/// A stack of refinements applied on top of a base [`LayoutStyle`]. Pushing
+/// a refinement (e.g. from a set-rule) is cheap; materializing the
+/// effective style folds the stack onto a clone of the base.
+///
+/// `layout` descends into a scope via [`State::refined`](super::State::refined),
+/// which clones the chain and pushes the scope's refinement onto the
+/// clone — so there's never a shared chain to pop from; the parent's
+/// chain is simply untouched once the clone is dropped at scope exit.
+#[derive(Debug, Default, Clone)]
+pub struct StyleChain {
+    base: LayoutStyle,
+    refinements: Vec<LayoutStyleRefinement>,
+}
+
+impl StyleChain {
+    pub fn new(base: LayoutStyle) -> Self {
+        Self { base, refinements: vec![] }
+    }
+
+    /// Pushes a new refinement that applies on top of everything already in
+    /// the chain, as happens when `layout` descends into a scope with local
+    /// overrides.
+    pub fn push(&mut self, refinement: LayoutStyleRefinement) {
+        self.refinements.push(refinement);
+    }
+
+    /// Folds the refinement stack onto the base style, producing the style
+    /// that's currently in effect.
+    pub fn resolve(&self) -> LayoutStyle {
+        let mut style = self.base.clone();
+        for refinement in &self.refinements {
+            style.refine(refinement);
+        }
+        style
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_style_refine_only_overwrites_fields_that_are_set() {
+        let mut style = TextStyle { dir: Dir::LTR, size: 10.0, justify: false };
+        style.refine(&TextStyleRefinement { dir: None, size: Some(12.0), justify: None });
+        assert_eq!(style.dir, Dir::LTR);
+        assert_eq!(style.size, 12.0);
+        assert_eq!(style.justify, false);
+    }
+
+    #[test]
+    fn page_style_refine_applies_margin_to_the_margins_field() {
+        let mut style = PageStyle::default();
+        let margin = Sides { left: 1.0, top: 2.0, right: 3.0, bottom: 4.0 };
+        style.refine(&PageStyleRefinement {
+            size: None,
+            margin: Some(margin),
+            columns: None,
+            gutter: None,
+            balance_columns: None,
+        });
+        assert_eq!(style.margins, margin);
+    }
+
+    #[test]
+    fn layout_style_refine_dispatches_to_both_sections() {
+        let mut style = LayoutStyle::default();
+        let refinement = LayoutStyleRefinement {
+            text: TextStyleRefinement { dir: None, size: None, justify: Some(true) },
+            page: PageStyleRefinement {
+                size: None,
+                margin: None,
+                columns: Some(2),
+                gutter: None,
+                balance_columns: None,
+            },
+        };
+        style.refine(&refinement);
+        assert_eq!(style.text.justify, true);
+        assert_eq!(style.page.columns, 2);
+    }
+
+    #[test]
+    fn style_chain_resolve_folds_refinements_in_push_order() {
+        let mut chain = StyleChain::new(LayoutStyle::default());
+        chain.push(LayoutStyleRefinement {
+            text: TextStyleRefinement { dir: None, size: Some(10.0), justify: None },
+            page: PageStyleRefinement::default(),
+        });
+        chain.push(LayoutStyleRefinement {
+            text: TextStyleRefinement { dir: None, size: Some(20.0), justify: None },
+            page: PageStyleRefinement::default(),
+        });
+
+        let resolved = chain.resolve();
+        assert_eq!(resolved.text.size, 20.0);
+    }
+
+    #[test]
+    fn style_chain_resolve_leaves_the_base_untouched() {
+        let base = LayoutStyle::default();
+        let mut chain = StyleChain::new(base.clone());
+        chain.push(LayoutStyleRefinement {
+            text: TextStyleRefinement { dir: None, size: Some(99.0), justify: None },
+            page: PageStyleRefinement::default(),
+        });
+        chain.resolve();
+        assert_eq!(chain.base.text.size, base.text.size);
+    }
+}
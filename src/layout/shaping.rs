@@ -0,0 +1,279 @@
+//! Shaping of text into positioned glyph layouts.
+//!
+//! Runs the Unicode Bidirectional Algorithm over each text node before
+//! shaping so that mixed-direction and fully RTL text (Arabic, Hebrew, ...)
+//! ends up with correct glyph order and advances.
+
+use crate::font::SharedFontLoader;
+use crate::geom::Dim;
+use crate::style::TextStyle;
+
+use super::primitive::Dir;
+use super::Layout;
+
+/// Options that configure how a run of text is shaped.
+pub struct ShapeOptions<'a> {
+    /// The font loader used to find glyphs for the text.
+    pub loader: &'a mut SharedFontLoader,
+    /// The text style (font, size, ...) to shape with.
+    pub style: &'a TextStyle,
+    /// The base direction of the paragraph this text belongs to. Used to
+    /// resolve the embedding levels of directionally neutral characters.
+    pub dir: Dir,
+}
+
+/// One maximal run of text at a single bidi embedding level.
+struct BidiRun {
+    /// Byte range of this run within the original text.
+    range: std::ops::Range<usize>,
+    /// The resolved embedding level, where odd levels are right-to-left.
+    level: u8,
+}
+
+/// Shapes `text` into a [`Layout`], running the bidi algorithm first so that
+/// runs of opposite direction are shaped and ordered correctly.
+pub async fn shape(text: &str, mut options: ShapeOptions<'_>) -> Layout {
+    let levels = resolve_levels(text, options.dir);
+    let runs = runs_from_levels(text, &levels);
+    let visual = reorder(&runs);
+
+    let mut layout = Layout::new(Dim::new(0.0, 0.0, 0.0));
+    let mut x = 0.0;
+
+    for run in visual {
+        let slice = &text[run.range.clone()];
+        let run_dir = if run.level % 2 == 1 { Dir::RTL } else { Dir::LTR };
+        let shaped = shape_run(slice, run_dir, &mut options).await;
+
+        layout.dim.height = layout.dim.height.max(shaped.dim.height);
+        layout.dim.depth = layout.dim.depth.max(shaped.dim.depth);
+        layout.push_layout(crate::geom::Point::new(x, 0.0), shaped.clone());
+        x += shaped.size().width;
+    }
+
+    layout.dim.width = x;
+    layout
+}
+
+/// Shapes a single directional run, reversing character order and
+/// substituting mirrored glyphs (brackets and parentheses) when the run is
+/// right-to-left.
+async fn shape_run(text: &str, dir: Dir, options: &mut ShapeOptions<'_>) -> Layout {
+    let text = if dir == Dir::RTL { mirror(text) } else { text.to_string() };
+    shape_ltr(&text, options).await
+}
+
+/// Shapes already-directionally-resolved text left to right. This is the
+/// low-level routine the old single-direction `shape` used to be; it now
+/// only ever sees one run at a time.
+async fn shape_ltr(text: &str, options: &mut ShapeOptions<'_>) -> Layout {
+    let font = options.loader.select(options.style).await;
+    font.shape(text, options.style)
+}
+
+/// Reverses a right-to-left run's character order (`reorder` only reverses
+/// the relative order of whole runs against each other, per UAX #9 rule L2 —
+/// the characters within a single run still need flipping into visual
+/// order), and substitutes paired punctuation (brackets, parentheses, angle
+/// brackets) with their mirrored counterpart for display.
+fn mirror(text: &str) -> String {
+    text.chars()
+        .rev()
+        .map(|c| match c {
+            '(' => ')',
+            ')' => '(',
+            '[' => ']',
+            ']' => '[',
+            '{' => '}',
+            '}' => '{',
+            '<' => '>',
+            '>' => '<',
+            other => other,
+        })
+        .collect()
+}
+
+/// Resolves a per-character embedding level for `text` given a paragraph
+/// base direction, per the Unicode Bidirectional Algorithm (UAX #9). This
+/// is a simplified implementation covering strong directional characters
+/// and resolving neutrals to the surrounding run's level.
+fn resolve_levels(text: &str, base: Dir) -> Vec<u8> {
+    let base_level: u8 = if base == Dir::RTL { 1 } else { 0 };
+    let mut levels = Vec::with_capacity(text.chars().count());
+    let mut neutral = Vec::with_capacity(levels.capacity());
+
+    for ch in text.chars() {
+        match bidi_class(ch) {
+            BidiClass::Strong(Dir::LTR) => {
+                levels.push(0);
+                neutral.push(false);
+            }
+            BidiClass::Strong(Dir::RTL) => {
+                levels.push(1);
+                neutral.push(false);
+            }
+            BidiClass::Strong(_) => {
+                levels.push(base_level);
+                neutral.push(false);
+            }
+            BidiClass::Neutral => {
+                // Placeholder level, overwritten by the resolution pass
+                // below once the neutral run's surrounding levels are known.
+                levels.push(base_level);
+                neutral.push(true);
+            }
+        }
+    }
+
+    // Resolve neutral runs (UAX #9 rules N1/N2, simplified): a maximal run
+    // of neutrals takes the level of the strong text on both sides when the
+    // two agree, and otherwise falls back to the paragraph's base level —
+    // the same fallback used for a neutral run at either edge of the text,
+    // where the missing side is treated as the base level (sos/eos).
+    let mut i = 0;
+    while i < levels.len() {
+        if !neutral[i] {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < levels.len() && neutral[i] {
+            i += 1;
+        }
+
+        let before = if start > 0 { levels[start - 1] } else { base_level };
+        let after = if i < levels.len() { levels[i] } else { base_level };
+        let resolved = if before == after { before } else { base_level };
+
+        for level in &mut levels[start..i] {
+            *level = resolved;
+        }
+    }
+
+    levels
+}
+
+enum BidiClass {
+    Strong(Dir),
+    Neutral,
+}
+
+/// Classifies a character's intrinsic directionality. Only the ranges
+/// needed to distinguish Latin text from Arabic/Hebrew text are covered;
+/// everything else is treated as directionally neutral.
+fn bidi_class(c: char) -> BidiClass {
+    match c {
+        '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}' => {
+            BidiClass::Strong(Dir::RTL)
+        }
+        c if c.is_alphabetic() => BidiClass::Strong(Dir::LTR),
+        _ => BidiClass::Neutral,
+    }
+}
+
+/// Segments `text` into maximal runs of characters sharing the same
+/// embedding level.
+fn runs_from_levels(text: &str, levels: &[u8]) -> Vec<BidiRun> {
+    let mut runs = vec![];
+    let mut indices = text.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+    indices.push(text.len());
+
+    let mut start = 0;
+    for i in 1..=levels.len() {
+        if i == levels.len() || levels[i] != levels[start] {
+            runs.push(BidiRun {
+                range: indices[start]..indices[i],
+                level: levels[start],
+            });
+            start = i;
+        }
+    }
+
+    runs
+}
+
+/// Reorders runs for display: sequences of runs at or above each odd level
+/// are reversed, from the highest level down to the lowest, per UAX #9's
+/// L2 rule. The result is the left-to-right visual order of runs.
+fn reorder(runs: &[BidiRun]) -> Vec<BidiRun> {
+    let mut visual: Vec<BidiRun> =
+        runs.iter().map(|r| BidiRun { range: r.range.clone(), level: r.level }).collect();
+
+    let max_level = visual.iter().map(|r| r.level).max().unwrap_or(0);
+    if max_level == 0 {
+        return visual;
+    }
+
+    for level in (1..=max_level).rev() {
+        let mut i = 0;
+        while i < visual.len() {
+            if visual[i].level >= level {
+                let start = i;
+                while i < visual.len() && visual[i].level >= level {
+                    i += 1;
+                }
+                visual[start..i].reverse();
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    visual
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_run_between_agreeing_strong_runs_takes_their_level() {
+        // "a  b" (two spaces are neutral) sits between two LTR letters, so
+        // the neutrals should resolve to LTR (level 0) rather than falling
+        // back to the base level.
+        let levels = resolve_levels("a  b", Dir::LTR);
+        assert_eq!(levels, vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn neutral_run_between_conflicting_strong_runs_falls_back_to_base_level() {
+        // Hebrew letter, a neutral space, then a Latin letter: the
+        // surrounding strong runs disagree (RTL vs LTR), so the space in
+        // between must fall back to the paragraph's base level.
+        let levels = resolve_levels("\u{05D0} a", Dir::LTR);
+        assert_eq!(levels, vec![1, 0, 0]);
+    }
+
+    #[test]
+    fn neutral_run_at_the_edge_of_the_text_falls_back_to_base_level() {
+        // A leading neutral run has no strong text before it (sos), and the
+        // strong run after it is RTL — since the missing side is treated as
+        // the LTR base level, the two sides disagree and the neutrals fall
+        // back to the base level rather than taking on RTL.
+        let levels = resolve_levels("  \u{05D0}", Dir::LTR);
+        assert_eq!(levels, vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn runs_from_levels_splits_on_every_level_change() {
+        let levels = vec![0, 0, 1, 1, 0];
+        let runs = runs_from_levels("ab\u{05D0}\u{05D1}c", &levels);
+        let ranges: Vec<_> = runs.iter().map(|r| (r.range.clone(), r.level)).collect();
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(ranges[1].1, 1);
+    }
+
+    #[test]
+    fn reorder_reverses_only_the_runs_at_or_above_each_odd_level() {
+        let runs = vec![
+            BidiRun { range: 0..1, level: 0 },
+            BidiRun { range: 1..2, level: 1 },
+            BidiRun { range: 2..3, level: 1 },
+            BidiRun { range: 3..4, level: 0 },
+        ];
+        let visual = reorder(&runs);
+        let order: Vec<_> = visual.iter().map(|r| r.range.start).collect();
+        assert_eq!(order, vec![0, 2, 1, 3]);
+    }
+}